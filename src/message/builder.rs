@@ -0,0 +1,166 @@
+//! A composable way of assembling an arbitrary IRC message, including
+//! IRCv3 tags and a prefix, in contrast to the fixed-shape constructor
+//! functions in the parent module.
+
+use std::borrow::Cow;
+
+use error::Result;
+use super::Message;
+use super::parser;
+
+/// Incrementally assembles a syntactically valid IRC line and parses it
+/// back into a `Message`, so the result has the same range-indexed
+/// representation as a message read off the wire.
+pub struct MessageBuilder {
+    command: String,
+    tags: Vec<(String, Option<String>)>,
+    prefix: Option<(String, Option<String>, Option<String>)>,
+    args: Vec<String>,
+    trailing: Option<String>,
+}
+
+impl MessageBuilder {
+    /// Starts building a message for the given command, e.g. `"PRIVMSG"`.
+    pub fn new<C: Into<String>>(command: C) -> MessageBuilder {
+        MessageBuilder {
+            command: command.into(),
+            tags: Vec::new(),
+            prefix: None,
+            args: Vec::new(),
+            trailing: None,
+        }
+    }
+
+    /// Sets the message's prefix to `nick[!user][@host]`.
+    pub fn prefix<N, U, H>(mut self, nick: N, user: Option<U>, host: Option<H>) -> MessageBuilder
+        where N: Into<String>, U: Into<String>, H: Into<String>
+    {
+        self.prefix = Some((nick.into(), user.map(Into::into), host.map(Into::into)));
+        self
+    }
+
+    /// Adds an IRCv3 tag. Its value, if any, is escaped per the
+    /// message-tags spec when the message is built.
+    pub fn tag<K, V>(mut self, key: K, value: Option<V>) -> MessageBuilder
+        where K: Into<String>, V: Into<String>
+    {
+        self.tags.push((key.into(), value.map(Into::into)));
+        self
+    }
+
+    /// Appends a plain, non-trailing argument.
+    pub fn arg<A: Into<String>>(mut self, arg: A) -> MessageBuilder {
+        self.args.push(arg.into());
+        self
+    }
+
+    /// Sets the trailing argument, which may contain spaces or be empty.
+    pub fn trailing<T: Into<String>>(mut self, trailing: T) -> MessageBuilder {
+        self.trailing = Some(trailing.into());
+        self
+    }
+
+    /// Assembles the accumulated pieces into a raw IRC line and parses it
+    /// back into a `Message`.
+    pub fn build(self) -> Result<Message> {
+        let mut line = String::new();
+
+        if !self.tags.is_empty() {
+            line.push('@');
+            for (index, &(ref key, ref value)) in self.tags.iter().enumerate() {
+                if index > 0 {
+                    line.push(';');
+                }
+                line.push_str(key);
+                if let Some(ref value) = *value {
+                    line.push('=');
+                    line.push_str(&escape(value));
+                }
+            }
+            line.push(' ');
+        }
+
+        if let Some((ref nick, ref user, ref host)) = self.prefix {
+            line.push(':');
+            line.push_str(nick);
+            if let Some(ref user) = *user {
+                line.push('!');
+                line.push_str(user);
+            }
+            if let Some(ref host) = *host {
+                line.push('@');
+                line.push_str(host);
+            }
+            line.push(' ');
+        }
+
+        line.push_str(&self.command);
+
+        for arg in &self.args {
+            line.push(' ');
+            line.push_str(arg);
+        }
+
+        if let Some(ref trailing) = self.trailing {
+            line.push(' ');
+            if trailing.is_empty() || trailing.contains(' ') || trailing.starts_with(':') {
+                line.push(':');
+            }
+            line.push_str(trailing);
+        }
+
+        parser::parse_message(line)
+    }
+}
+
+/// Escapes a tag value per the message-tags spec: the inverse of the
+/// decoding `tag_escape::unescape` performs.
+fn escape(value: &str) -> Cow<str> {
+    if !value.contains(|c| c == ';' || c == ' ' || c == '\\' || c == '\r' || c == '\n') {
+        return Cow::Borrowed(value);
+    }
+
+    let mut result = String::with_capacity(value.len());
+
+    for c in value.chars() {
+        match c {
+            ';' => result.push_str("\\:"),
+            ' ' => result.push_str("\\s"),
+            '\\' => result.push_str("\\\\"),
+            '\r' => result.push_str("\\r"),
+            '\n' => result.push_str("\\n"),
+            other => result.push(other),
+        }
+    }
+
+    Cow::Owned(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MessageBuilder;
+
+    #[test]
+    fn trailing_starting_with_colon_round_trips() {
+        let message = MessageBuilder::new("PRIVMSG").arg("#chan").trailing(":)").build().unwrap();
+        let mut args = message.raw_args();
+        assert_eq!(args.next(), Some("#chan"));
+        assert_eq!(args.next(), Some(":)"));
+    }
+
+    #[test]
+    fn trailing_with_a_space_round_trips() {
+        let message = MessageBuilder::new("PRIVMSG").arg("#chan").trailing("hi there").build().unwrap();
+        let mut args = message.raw_args();
+        assert_eq!(args.next(), Some("#chan"));
+        assert_eq!(args.next(), Some("hi there"));
+    }
+
+    #[test]
+    fn empty_trailing_round_trips() {
+        let message = MessageBuilder::new("PRIVMSG").arg("#chan").trailing("").build().unwrap();
+        let mut args = message.raw_args();
+        assert_eq!(args.next(), Some("#chan"));
+        assert_eq!(args.next(), Some(""));
+    }
+}
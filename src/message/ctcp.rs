@@ -0,0 +1,198 @@
+//! Support for the Client-To-Client Protocol (CTCP), a convention layered
+//! over `PRIVMSG`/`NOTICE` where the trailing argument is wrapped in `\x01`
+//! bytes to carry a tag such as `ACTION`, `VERSION` or `PING` plus
+//! parameters.
+
+use std::borrow::Cow;
+
+use error::Result;
+use super::{privmsg, Message};
+
+const DELIM: char = '\u{1}';
+const QUOTE: char = '\u{10}';
+
+impl Message {
+    /// If this is a PRIVMSG/NOTICE whose trailing argument is
+    /// CTCP-delimited, returns its uppercased tag and parameters. Returns
+    /// `None` for any other command, and for PRIVMSG/NOTICE messages that
+    /// aren't CTCP, i.e. plain chat.
+    pub fn ctcp(&self) -> Option<(Cow<str>, Cow<str>)> {
+        match self.raw_command().to_uppercase().as_str() {
+            "PRIVMSG" | "NOTICE" => {}
+            _ => return None,
+        }
+
+        let body = self.raw_args().last()?;
+        parse(body)
+    }
+}
+
+fn parse(body: &str) -> Option<(Cow<str>, Cow<str>)> {
+    if body.len() < 2 || !body.starts_with(DELIM) || !body.ends_with(DELIM) {
+        return None;
+    }
+
+    let inner = &body[DELIM.len_utf8()..body.len() - DELIM.len_utf8()];
+
+    match dequote(inner) {
+        Cow::Borrowed(s) => {
+            let mut parts = s.splitn(2, ' ');
+            let tag = parts.next().unwrap_or("");
+            let params = parts.next().unwrap_or("");
+            Some((uppercase(tag), Cow::Borrowed(params)))
+        }
+        Cow::Owned(s) => {
+            let mut parts = s.splitn(2, ' ');
+            let tag = parts.next().unwrap_or("").to_uppercase();
+            let params = parts.next().unwrap_or("").to_string();
+            Some((Cow::Owned(tag), Cow::Owned(params)))
+        }
+    }
+}
+
+fn uppercase(tag: &str) -> Cow<str> {
+    if tag.bytes().any(|b| b.is_ascii_lowercase()) {
+        Cow::Owned(tag.to_uppercase())
+    } else {
+        Cow::Borrowed(tag)
+    }
+}
+
+/// Reverses CTCP "X-quoting": `\x10` is the quote character, with
+/// `\x100` decoding to NUL, `\x10n` to LF, `\x10r` to CR, `\x10\x10` to a
+/// literal `\x10`, so embedded control characters round-trip safely.
+fn dequote(raw: &str) -> Cow<str> {
+    if !raw.contains(QUOTE) {
+        return Cow::Borrowed(raw);
+    }
+
+    let mut result = String::with_capacity(raw.len());
+    let mut chars = raw.chars();
+
+    while let Some(c) = chars.next() {
+        if c != QUOTE {
+            result.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('0') => result.push('\0'),
+            Some('n') => result.push('\n'),
+            Some('r') => result.push('\r'),
+            Some(q) if q == QUOTE => result.push(QUOTE),
+            Some(other) => result.push(other),
+            None => {}
+        }
+    }
+
+    Cow::Owned(result)
+}
+
+/// Constructs a PRIVMSG wrapping `tag` and `params` in CTCP delimiters, for
+/// queries such as `VERSION` or `PING`.
+pub fn ctcp_query<T, G, P>(target: T, tag: G, params: P) -> Result<Message>
+    where T: Into<String>, G: Into<String>, P: Into<String>
+{
+    let tag = tag.into();
+    let params = params.into();
+
+    let tag = quote(&tag);
+    let params = quote(&params);
+
+    let body = if params.is_empty() {
+        format!("{}{}{}", DELIM, tag, DELIM)
+    } else {
+        format!("{}{} {}{}", DELIM, tag, params, DELIM)
+    };
+
+    privmsg(target, body)
+}
+
+/// CTCP "X-quoting": the inverse of `dequote`. Escapes NUL, LF, CR and a
+/// literal quote character so they round-trip safely once wrapped in
+/// `\x01` and sent over the wire.
+fn quote(raw: &str) -> Cow<str> {
+    if !raw.contains(|c| c == '\0' || c == '\n' || c == '\r' || c == QUOTE) {
+        return Cow::Borrowed(raw);
+    }
+
+    let mut result = String::with_capacity(raw.len());
+
+    for c in raw.chars() {
+        match c {
+            '\0' => {
+                result.push(QUOTE);
+                result.push('0');
+            }
+            '\n' => {
+                result.push(QUOTE);
+                result.push('n');
+            }
+            '\r' => {
+                result.push(QUOTE);
+                result.push('r');
+            }
+            c if c == QUOTE => {
+                result.push(QUOTE);
+                result.push(QUOTE);
+            }
+            other => result.push(other),
+        }
+    }
+
+    Cow::Owned(result)
+}
+
+/// Constructs a PRIVMSG containing a CTCP `ACTION` (an "emote"), e.g. `/me`.
+pub fn action<T, M>(target: T, text: M) -> Result<Message>
+    where T: Into<String>, M: Into<String>
+{
+    ctcp_query(target, "ACTION", text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{dequote, parse, quote};
+    use super::super::MessageBuilder;
+    use std::borrow::Cow;
+
+    #[test]
+    fn ctcp_ignores_non_privmsg_notice_commands() {
+        let message = MessageBuilder::new("PING").trailing("\u{1}VERSION\u{1}").build().unwrap();
+        assert!(message.ctcp().is_none());
+    }
+
+    #[test]
+    fn parse_recognizes_a_tag_and_params() {
+        let (tag, params) = parse("\u{1}ACTION waves\u{1}").unwrap();
+        assert_eq!(tag, "ACTION");
+        assert_eq!(params, "waves");
+    }
+
+    #[test]
+    fn parse_uppercases_a_lowercase_tag() {
+        let (tag, params) = parse("\u{1}version\u{1}").unwrap();
+        assert_eq!(tag, "VERSION");
+        assert_eq!(params, "");
+    }
+
+    #[test]
+    fn parse_returns_none_without_delimiters() {
+        assert!(parse("just chat").is_none());
+    }
+
+    #[test]
+    fn dequote_borrows_when_there_is_nothing_to_decode() {
+        match dequote("plain") {
+            Cow::Borrowed(s) => assert_eq!(s, "plain"),
+            Cow::Owned(_) => panic!("expected a borrowed value"),
+        }
+    }
+
+    #[test]
+    fn quote_and_dequote_round_trip_control_characters() {
+        let original = "null\0 lf\n cr\r dle\u{10}";
+        let quoted = quote(original);
+        assert_eq!(dequote(&quoted), original);
+    }
+}
@@ -0,0 +1,160 @@
+//! Helpers for the mIRC/IRCv3 in-band formatting codes that show up inside
+//! PRIVMSG/NOTICE bodies: bold, italic, underline, strikethrough,
+//! monospace, reverse video, color, and the reset code that clears all of
+//! the above.
+
+use std::borrow::Cow;
+use std::iter::Peekable;
+use std::str::Chars;
+
+const BOLD: char = '\u{02}';
+const ITALIC: char = '\u{1D}';
+const UNDERLINE: char = '\u{1F}';
+const STRIKETHROUGH: char = '\u{1E}';
+const MONOSPACE: char = '\u{11}';
+const REVERSE: char = '\u{16}';
+const RESET: char = '\u{0F}';
+const COLOR: char = '\u{03}';
+
+fn is_code(c: char) -> bool {
+    match c {
+        BOLD | ITALIC | UNDERLINE | STRIKETHROUGH | MONOSPACE | REVERSE | RESET | COLOR => true,
+        _ => false,
+    }
+}
+
+/// Removes all formatting codes from `text`, yielding the plain text
+/// underneath. Useful for logging and for matching against message
+/// content.
+pub fn strip(text: &str) -> Cow<str> {
+    if !text.contains(is_code) {
+        return Cow::Borrowed(text);
+    }
+
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c == COLOR {
+            skip_color(&mut chars);
+        } else if !is_code(c) {
+            result.push(c);
+        }
+    }
+
+    Cow::Owned(result)
+}
+
+// `\x03` is optionally followed by `FF[,BB]`, each component at most two
+// digits, so that `\x034,2text` (fg 4, bg 2) and `\x0342` (fg 42) are
+// disambiguated the same way real clients do. The `,BB` part only counts
+// as a background spec when a foreground digit actually preceded it, so
+// `\x03,5red` (no foreground) leaves the literal `,5red` untouched.
+fn skip_color(chars: &mut Peekable<Chars>) {
+    let fg_digits = skip_digits(chars, 2);
+
+    if fg_digits > 0 && chars.peek() == Some(&',') {
+        chars.next();
+        skip_digits(chars, 2);
+    }
+}
+
+fn skip_digits(chars: &mut Peekable<Chars>, max: usize) -> usize {
+    let mut skipped = 0;
+
+    for _ in 0..max {
+        match chars.peek() {
+            Some(c) if c.is_ascii_digit() => {
+                chars.next();
+                skipped += 1;
+            }
+            _ => break,
+        }
+    }
+
+    skipped
+}
+
+/// Wraps `text` in bold codes.
+pub fn bold(text: &str) -> String {
+    format!("{}{}{}", BOLD, text, RESET)
+}
+
+/// Wraps `text` in italic codes.
+pub fn italic(text: &str) -> String {
+    format!("{}{}{}", ITALIC, text, RESET)
+}
+
+/// Wraps `text` in underline codes.
+pub fn underline(text: &str) -> String {
+    format!("{}{}{}", UNDERLINE, text, RESET)
+}
+
+/// Wraps `text` in strikethrough codes.
+pub fn strikethrough(text: &str) -> String {
+    format!("{}{}{}", STRIKETHROUGH, text, RESET)
+}
+
+/// Wraps `text` in monospace codes.
+pub fn monospace(text: &str) -> String {
+    format!("{}{}{}", MONOSPACE, text, RESET)
+}
+
+/// Wraps `text` in reverse-video codes.
+pub fn reverse(text: &str) -> String {
+    format!("{}{}{}", REVERSE, text, RESET)
+}
+
+const MAX_COLOR: u8 = 98;
+
+/// Wraps `text` in the given foreground (and optional background) color,
+/// using the mIRC numeric palette (0-15, or 16-98 for the extended
+/// palette). Values above 98 are clamped to it, since `strip` only ever
+/// consumes up to two digits per component and a wider value would
+/// disagree with it on how much of the input is a color code.
+pub fn color(fg: u8, bg: Option<u8>, text: &str) -> String {
+    let fg = clamp_color(fg);
+    match bg {
+        Some(bg) => format!("{}{},{}{}{}", COLOR, fg, clamp_color(bg), text, RESET),
+        None => format!("{}{}{}{}", COLOR, fg, text, RESET),
+    }
+}
+
+fn clamp_color(value: u8) -> u8 {
+    if value > MAX_COLOR {
+        MAX_COLOR
+    } else {
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{bold, color, strip};
+
+    #[test]
+    fn strip_removes_bold_and_reset() {
+        assert_eq!(strip(&bold("hi")), "hi");
+    }
+
+    #[test]
+    fn strip_disambiguates_fg_comma_bg_from_two_digit_fg() {
+        assert_eq!(strip("\u{3}4,2text"), "text");
+        assert_eq!(strip("\u{3}42text"), "text");
+    }
+
+    #[test]
+    fn strip_leaves_a_comma_with_no_preceding_digits_untouched() {
+        assert_eq!(strip("\u{3},5red"), ",5red");
+    }
+
+    #[test]
+    fn strip_round_trips_color_with_background() {
+        assert_eq!(strip(&color(4, Some(2), "text")), "text");
+    }
+
+    #[test]
+    fn color_clamps_out_of_range_values() {
+        assert_eq!(strip(&color(150, None, "text")), "text");
+    }
+}
@@ -5,11 +5,24 @@
 //! messages to be sent to the server.
 
 mod parser;
+mod tag_escape;
+mod builder;
+mod ctcp;
+mod owned_command;
+pub mod format;
+
+pub use self::builder::MessageBuilder;
+pub use self::ctcp::{ctcp_query, action};
+pub use self::owned_command::Command;
 
 use error::Result;
-use command::{Command, ArgumentIter};
+// Aliased so the trait used for zero-copy dispatch doesn't clash with the
+// owned `Command` enum re-exported above.
+use command::{Command as CommandMatch, ArgumentIter};
 use tag::{Tag, TagIter};
+use self::tag_escape::{unescape, DecodedTagIter};
 
+use std::borrow::Cow;
 use std::ops::Range;
 
 #[derive(Clone, PartialEq, Eq, Debug)]
@@ -43,8 +56,15 @@ impl Message {
 
     /// A strongly typed interface for determining the type of the command
     /// and retrieving the values of the command.
-    pub fn command<'a, T>(&'a self) -> Option<T> where T : Command<'a> {
-        <T as Command>::try_match(self.raw_command(), self.raw_args())
+    pub fn command<'a, T>(&'a self) -> Option<T> where T : CommandMatch<'a> {
+        <T as CommandMatch>::try_match(self.raw_command(), self.raw_args())
+    }
+
+    /// Classifies this message's command into an owned `Command` value
+    /// that can be matched exhaustively and stored past the lifetime of
+    /// this message.
+    pub fn parsed_command(&self) -> Command {
+        Command::from_message(self)
     }
 
     /// A strongly type way of accessing a specified tag associated with
@@ -103,6 +123,26 @@ impl Message {
     pub fn raw_message(&self) -> &str {
         &self.message
     }
+
+    /// Get an iterator over this message's tags with their values decoded
+    /// according to the IRCv3 tag-value escaping rules, rather than the
+    /// verbatim bytes `raw_tags` yields.
+    pub fn tags_decoded(&self) -> DecodedTagIter {
+        DecodedTagIter::new(self.raw_tags())
+    }
+
+    /// Retrieve the decoded value of a specific tag, if present.
+    ///
+    /// Returns `None` if the tag is not present at all. Returns `Some(None)`
+    /// if the tag is present but has no value (e.g. the `bar` in
+    /// `@foo;bar`), and `Some(Some(value))` with the unescaped value
+    /// otherwise, so an empty value stays distinguishable from a value-less
+    /// tag.
+    pub fn tag_value(&self, key: &str) -> Option<Option<Cow<str>>> {
+        self.raw_tags()
+            .find(|&(tag_key, _)| tag_key == key)
+            .map(|(_, value)| value.map(unescape))
+    }
 }
 
 /// Constructs a message containing a PING command targeting the specified host.
@@ -164,3 +204,24 @@ pub fn created<T: Into<String>>(target: T, message: T) -> Result<Message> {
 pub fn serverinfo<T: Into<String>>(target: T, message: T) -> Result<Message> {
     Message::try_from(format!("004 {} :{}", target.into(), message.into()))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Message;
+
+    #[test]
+    fn tag_value_distinguishes_value_less_from_empty() {
+        let message = Message::try_from("@foo=;bar PRIVMSG #chan :hi".to_string()).unwrap();
+
+        assert_eq!(message.tag_value("foo"), Some(Some("".into())));
+        assert_eq!(message.tag_value("bar"), Some(None));
+        assert_eq!(message.tag_value("missing"), None);
+    }
+
+    #[test]
+    fn tag_value_decodes_escaped_values() {
+        let message = Message::try_from("@note=a\\sb PRIVMSG #chan :hi".to_string()).unwrap();
+
+        assert_eq!(message.tag_value("note"), Some(Some("a b".into())));
+    }
+}
@@ -0,0 +1,240 @@
+//! An owned, exhaustive representation of a message's command, for callers
+//! that want a single value to `match` over, clone, and store once the
+//! borrow on the parsed `Message` has ended.
+
+use std::fmt;
+
+use error::Result;
+use super::{Message, MessageBuilder};
+
+/// An owned IRC command and its arguments.
+///
+/// This complements the zero-copy `Command` trait, which matches one
+/// command shape at a time against a borrowed message: `Command` instead
+/// gives a single value that can be matched exhaustively, cloned, and
+/// stored after the message it came from is gone.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Command {
+    Privmsg(String, String),
+    Notice(String, String),
+    Join(String),
+    Part(String, Option<String>),
+    Nick(String),
+    User(String, String),
+    Ping(String),
+    Pong(String),
+    Mode(String, Vec<String>),
+    Quit(Option<String>),
+    Cap(Vec<String>),
+    /// A three-digit numeric reply and its arguments.
+    Numeric(u16, Vec<String>),
+    /// Any command this enum has no dedicated variant for.
+    Raw(String, Vec<String>),
+}
+
+impl Command {
+    /// Classifies a message's raw command and arguments into an owned
+    /// `Command`. Unknown alphabetic commands fall into `Raw` rather than
+    /// failing.
+    pub(crate) fn from_message(message: &Message) -> Command {
+        let command = message.raw_command();
+        let args: Vec<String> = message.raw_args().map(str::to_string).collect();
+
+        if command.len() == 3 && command.bytes().all(|b| b.is_ascii_digit()) {
+            // Safe: three ASCII digits is at most "999", which always fits a u16.
+            let code = command.parse::<u16>().unwrap();
+            return Command::Numeric(code, args);
+        }
+
+        match command.to_uppercase().as_str() {
+            "PRIVMSG" => {
+                let mut args = args.into_iter();
+                let target = args.next().unwrap_or_default();
+                let text = args.next().unwrap_or_default();
+                Command::Privmsg(target, text)
+            }
+            "NOTICE" => {
+                let mut args = args.into_iter();
+                let target = args.next().unwrap_or_default();
+                let text = args.next().unwrap_or_default();
+                Command::Notice(target, text)
+            }
+            "JOIN" => Command::Join(args.into_iter().next().unwrap_or_default()),
+            "PART" => {
+                let mut args = args.into_iter();
+                let channel = args.next().unwrap_or_default();
+                let reason = args.next();
+                Command::Part(channel, reason)
+            }
+            "NICK" => Command::Nick(args.into_iter().next().unwrap_or_default()),
+            "USER" => {
+                let mut args = args.into_iter();
+                let username = args.next().unwrap_or_default();
+                let real_name = args.last().unwrap_or_default();
+                Command::User(username, real_name)
+            }
+            "PING" => Command::Ping(args.into_iter().next().unwrap_or_default()),
+            "PONG" => Command::Pong(args.into_iter().next().unwrap_or_default()),
+            "MODE" => {
+                let mut args = args.into_iter();
+                let target = args.next().unwrap_or_default();
+                Command::Mode(target, args.collect())
+            }
+            "QUIT" => Command::Quit(args.into_iter().next()),
+            "CAP" => Command::Cap(args),
+            _ => Command::Raw(command.to_string(), args),
+        }
+    }
+
+    /// Serializes this command back into a `Message`, routing through
+    /// `MessageBuilder` so the result round-trips through the parser like
+    /// any other constructed message.
+    pub fn to_message(&self) -> Result<Message> {
+        match *self {
+            Command::Privmsg(ref target, ref text) => {
+                MessageBuilder::new("PRIVMSG").arg(target.clone()).trailing(text.clone()).build()
+            }
+            Command::Notice(ref target, ref text) => {
+                MessageBuilder::new("NOTICE").arg(target.clone()).trailing(text.clone()).build()
+            }
+            Command::Join(ref channel) => MessageBuilder::new("JOIN").arg(channel.clone()).build(),
+            Command::Part(ref channel, ref reason) => {
+                let mut builder = MessageBuilder::new("PART").arg(channel.clone());
+                if let Some(ref reason) = *reason {
+                    builder = builder.trailing(reason.clone());
+                }
+                builder.build()
+            }
+            Command::Nick(ref nick) => MessageBuilder::new("NICK").arg(nick.clone()).build(),
+            Command::User(ref username, ref real_name) => {
+                MessageBuilder::new("USER")
+                    .arg(username.clone())
+                    .arg("0")
+                    .arg("*")
+                    .trailing(real_name.clone())
+                    .build()
+            }
+            Command::Ping(ref host) => MessageBuilder::new("PING").trailing(host.clone()).build(),
+            Command::Pong(ref host) => MessageBuilder::new("PONG").arg(host.clone()).build(),
+            Command::Mode(ref target, ref modes) => {
+                with_args(MessageBuilder::new("MODE").arg(target.clone()), modes).build()
+            }
+            Command::Quit(ref reason) => {
+                let mut builder = MessageBuilder::new("QUIT");
+                if let Some(ref reason) = *reason {
+                    builder = builder.trailing(reason.clone());
+                }
+                builder.build()
+            }
+            Command::Cap(ref args) => with_args(MessageBuilder::new("CAP"), args).build(),
+            Command::Numeric(code, ref args) => {
+                if code > 999 {
+                    return Err(format!("numeric command code {} is out of the valid 0-999 range",
+                                        code)
+                        .into());
+                }
+                with_args(MessageBuilder::new(format!("{:03}", code)), args).build()
+            }
+            Command::Raw(ref command, ref args) => {
+                with_args(MessageBuilder::new(command.clone()), args).build()
+            }
+        }
+    }
+}
+
+/// Appends `args` to `builder`, routing the final one through `trailing` so
+/// that a space-containing or empty last argument survives the round trip
+/// through the builder with its `:` marker intact.
+fn with_args(builder: MessageBuilder, args: &[String]) -> MessageBuilder {
+    match args.split_last() {
+        Some((last, rest)) => {
+            let mut builder = builder;
+            for arg in rest {
+                builder = builder.arg(arg.clone());
+            }
+            builder.trailing(last.clone())
+        }
+        None => builder,
+    }
+}
+
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.to_message() {
+            Ok(message) => write!(f, "{}", message.raw_message()),
+            Err(_) => write!(f, "{:?}", self),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Command;
+
+    fn round_trip(command: Command) {
+        let message = command.to_message().unwrap();
+        assert_eq!(message.parsed_command(), command);
+    }
+
+    #[test]
+    fn privmsg_round_trips_leading_colon_text() {
+        round_trip(Command::Privmsg("#chan".to_string(), ":) hi".to_string()));
+    }
+
+    #[test]
+    fn notice_round_trips_leading_colon_text() {
+        round_trip(Command::Notice("#chan".to_string(), ":-P".to_string()));
+    }
+
+    #[test]
+    fn part_round_trips_leading_colon_reason() {
+        round_trip(Command::Part("#chan".to_string(), Some(":(".to_string())));
+    }
+
+    #[test]
+    fn quit_round_trips_leading_colon_reason() {
+        round_trip(Command::Quit(Some(":(".to_string())));
+    }
+
+    #[test]
+    fn mode_round_trips_with_multiple_args() {
+        round_trip(Command::Mode("#chan".to_string(),
+                                  vec!["+o".to_string(), "nick".to_string()]));
+    }
+
+    #[test]
+    fn mode_round_trips_with_no_args() {
+        round_trip(Command::Mode("#chan".to_string(), Vec::new()));
+    }
+
+    #[test]
+    fn raw_round_trips_with_args() {
+        round_trip(Command::Raw("WHOIS".to_string(), vec!["nick".to_string()]));
+    }
+
+    #[test]
+    fn raw_round_trips_with_no_args() {
+        round_trip(Command::Raw("AWAY".to_string(), Vec::new()));
+    }
+
+    #[test]
+    fn numeric_round_trips() {
+        round_trip(Command::Numeric(372, vec!["nick".to_string(), "a motd line".to_string()]));
+    }
+
+    #[test]
+    fn numeric_rejects_codes_above_999() {
+        assert!(Command::Numeric(1234, Vec::new()).to_message().is_err());
+    }
+
+    #[test]
+    fn from_message_does_not_misclassify_a_leading_plus_as_numeric() {
+        use super::super::MessageBuilder;
+
+        let message = MessageBuilder::new("+12").arg("nick").build().unwrap();
+        match message.parsed_command() {
+            Command::Raw(ref command, _) => assert_eq!(command, "+12"),
+            other => panic!("expected Raw, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,87 @@
+//! Decoding for the value-escaping scheme the IRCv3 message-tags
+//! specification mandates for tag values.
+
+use std::borrow::Cow;
+
+use tag::TagIter;
+
+/// Un-escapes a raw tag value according to the message-tags spec: `\:` to
+/// `;`, `\s` to space, `\\` to `\`, `\r` to CR, `\n` to LF, a backslash
+/// followed by anything else drops the backslash, and a trailing lone
+/// backslash is dropped.
+pub(crate) fn unescape(raw: &str) -> Cow<str> {
+    if !raw.contains('\\') {
+        return Cow::Borrowed(raw);
+    }
+
+    let mut result = String::with_capacity(raw.len());
+    let mut chars = raw.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some(':') => result.push(';'),
+            Some('s') => result.push(' '),
+            Some('\\') => result.push('\\'),
+            Some('r') => result.push('\r'),
+            Some('n') => result.push('\n'),
+            Some(other) => result.push(other),
+            None => {}
+        }
+    }
+
+    Cow::Owned(result)
+}
+
+/// An iterator over a message's tags that decodes each value according to
+/// the IRCv3 tag-value escaping rules.
+pub struct DecodedTagIter<'a> {
+    inner: TagIter<'a>,
+}
+
+impl<'a> DecodedTagIter<'a> {
+    pub(crate) fn new(inner: TagIter<'a>) -> DecodedTagIter<'a> {
+        DecodedTagIter { inner: inner }
+    }
+}
+
+impl<'a> Iterator for DecodedTagIter<'a> {
+    type Item = (&'a str, Option<Cow<'a, str>>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(key, value)| (key, value.map(unescape)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::unescape;
+    use std::borrow::Cow;
+
+    #[test]
+    fn unescape_borrows_when_there_is_nothing_to_decode() {
+        match unescape("plain") {
+            Cow::Borrowed(s) => assert_eq!(s, "plain"),
+            Cow::Owned(_) => panic!("expected a borrowed value"),
+        }
+    }
+
+    #[test]
+    fn unescape_decodes_all_escape_sequences() {
+        assert_eq!(unescape("a\\:b\\sc\\\\d\\re\\nf"), "a;b c\\d\re\nf");
+    }
+
+    #[test]
+    fn unescape_drops_backslash_before_unknown_char() {
+        assert_eq!(unescape("a\\xb"), "axb");
+    }
+
+    #[test]
+    fn unescape_drops_trailing_lone_backslash() {
+        assert_eq!(unescape("abc\\"), "abc");
+    }
+}